@@ -20,6 +20,23 @@ pub enum MmapError {
     /// [POSIX](http://pubs.opengroup.org/onlinepubs/9699919799/functions/mmap.html).
     /// Not all platforms obey this, but this wrapper does.
     ErrZeroLength,
+    /// The mapping, or part of it, was locked, or the system was unable to complete the
+    /// synchronization because of a conflicting in-progress operation (`EBUSY`).
+    ErrBusy,
+    /// `offset + len` overflowed `usize` while computing the end of an access range.
+    ErrRangeOverflow,
+    /// The requested `(offset, len)` range does not fit within the mapped file, whose
+    /// current logical size is the third value.
+    ErrInvalidRange(usize, usize, usize),
+    /// A mutating call (`append`, `overwrite`, `write_*`, `drop_from_tail`) was made on a
+    /// `Mmap` opened with `MmapOptions::read_only`.
+    ErrReadOnly,
+    /// `append` or `drop_from_tail` was called on a `Mmap` opened with
+    /// `MmapOptions::copy_on_write`. Copy-on-write maps are fixed-size: growing or
+    /// shrinking one would require changing the backing file's length, which would defeat
+    /// the "never touches the file" guarantee (and, if done without it, SIGBUS on access
+    /// to a mapped page with no backing data).
+    ErrFixedSize,
     /// Unrecognized error. The inner value is the unrecognized errno.
     ErrUnknown(isize),
     /// # The following are Windows-specific
@@ -58,6 +75,21 @@ impl std::fmt::Display for MmapError {
             MmapError::ErrUnsupOffset => "Offset in virtual memory mode is unsupported",
             MmapError::ErrAlreadyExists => "File mapping for specified file already exists",
             MmapError::ErrZeroLength => "Zero-length mapping not allowed",
+            MmapError::ErrBusy => "Mapping is busy or a conflicting sync is in progress",
+            MmapError::ErrRangeOverflow => "offset + len overflowed while computing a range",
+            MmapError::ErrInvalidRange(offset, len, size) => {
+                return write!(
+                    out,
+                    "range [{}, {}) is out of bounds for a mapping of size {}",
+                    offset,
+                    offset + len,
+                    size
+                )
+            }
+            MmapError::ErrReadOnly => "Mmap was opened read-only and cannot be mutated",
+            MmapError::ErrFixedSize => {
+                "Mmap was opened copy-on-write and its logical size cannot change"
+            }
             MmapError::ErrUnknown(code) => return write!(out, "Unknown error = {}", code),
             MmapError::ErrVirtualAlloc(code) => {
                 return write!(out, "VirtualAlloc failure = {}", code)
@@ -1,10 +1,14 @@
 //! A simple API for treating a file basically as an infinite vector that can be written to at any
 //! point, appended to, read from and shrinken at will and in a very fast way.
 //!
-//! The file is memory-mapped with a libc call specifying basically an infinite memory size. But it
-//! doesn't consume that amount of memory. Should only be used on Linux and from a single caller/process.
-//! All write calls immediately call `sync_all` after them, which is not ideal, but maybe we'll improve
-//! later.
+//! The file is memory-mapped with a modest initial virtual memory reservation that grows
+//! (via `mremap`) whenever an append would outgrow it, so there is no fixed ceiling on file
+//! size. Should only be used on Linux and from a single caller/process.
+//! Appends and overwrites are not synced to disk automatically: call [`Mmap::flush`],
+//! [`Mmap::flush_async`] or [`Mmap::flush_range`] when you want the kernel to write dirty pages
+//! back, so callers can batch many writes and pay for a single `msync` instead of one per call.
+//! The exception is [`Mmap::drop_from_tail`], which calls `sync_all` on the file after
+//! truncating it.
 //!
 //! # Example
 //!
@@ -18,6 +22,7 @@
 //!     mmap.overwrite(0, b"Goodbye")?;
 //!     mmap.drop_from_tail(6)?;
 //!     mmap.append(b", world!")?;
+//!     mmap.flush()?;
 //!     Ok(())
 //! }
 //! ```
@@ -29,11 +34,174 @@ mod errors;
 use crate::errors::*;
 use crate::MmapError::*;
 
+/// The default initial virtual memory reservation for a new mapping, used when
+/// `MmapOptions::reserve` isn't called. The mapping grows past this via `mremap` as needed.
+const DEFAULT_RESERVE: usize = 1 << 20;
+
 /// A struct that represents a memory-mapped file.
 pub struct Mmap {
     file: fs::File,
     ptr: *mut u8,
+    /// The size of the live, logical region of the file, in bytes.
     pub size: u64,
+    /// The size of the current virtual memory reservation backing `ptr`. Always `>= size`;
+    /// grown via `mremap` when an append would push `size` past it.
+    capacity: usize,
+    mode: MapMode,
+}
+
+/// The protection/sharing mode a `Mmap` was opened with, as configured via `MmapOptions`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MapMode {
+    /// `PROT_READ | PROT_WRITE`, `MAP_SHARED`. Mutations are written back to the file.
+    ReadWrite,
+    /// `PROT_READ`, `MAP_SHARED`. No mutating call is allowed.
+    ReadOnly,
+    /// `PROT_READ | PROT_WRITE`, `MAP_PRIVATE`. Mutations are visible in-process only and
+    /// are never written back to the file; `flush`/`flush_async`/`flush_range` are no-ops.
+    CopyOnWrite,
+}
+
+/// A builder for configuring how a file is memory-mapped, modeled after the `memmap` crate's
+/// `MmapOptions`. The default mode is read-write and `MAP_SHARED`, with a
+/// `DEFAULT_RESERVE`-sized initial reservation that grows as the file grows.
+#[derive(Copy, Clone, Debug)]
+pub struct MmapOptions {
+    mode: MapMode,
+    reserve: usize,
+}
+
+impl Default for MmapOptions {
+    fn default() -> Self {
+        MmapOptions {
+            mode: MapMode::ReadWrite,
+            reserve: DEFAULT_RESERVE,
+        }
+    }
+}
+
+impl MmapOptions {
+    /// Creates a new `MmapOptions` builder in the default read-write, `MAP_SHARED` mode.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens the file read-only (`PROT_READ`, `MAP_SHARED`). Any mutating call (`append`,
+    /// `overwrite`, `write_*`, `drop_from_tail`) on the resulting `Mmap` fails fast with
+    /// `MmapError::ErrReadOnly`.
+    pub fn read_only(mut self) -> Self {
+        self.mode = MapMode::ReadOnly;
+        self
+    }
+
+    /// Opens the file for private copy-on-write edits (`PROT_READ | PROT_WRITE`,
+    /// `MAP_PRIVATE`), with the fd itself opened read-only since `MAP_PRIVATE` never
+    /// requires a writable fd. Mutations are visible in-process but never reach the
+    /// backing file, and `flush`/`flush_async`/`flush_range` become no-ops.
+    ///
+    /// Copy-on-write maps are fixed-size: `append` and `drop_from_tail` fail with
+    /// `MmapError::ErrFixedSize`, since changing the logical size would mean changing the
+    /// backing file's length, which would defeat the "never touches the file" guarantee.
+    pub fn copy_on_write(mut self) -> Self {
+        self.mode = MapMode::CopyOnWrite;
+        self
+    }
+
+    /// Sets the initial virtual memory reservation, in bytes. This is only a tuning hint to
+    /// avoid early `mremap` calls for files expected to grow large: the mapping still grows
+    /// (doubling) past this size automatically as the file grows.
+    pub fn reserve(mut self, bytes: usize) -> Self {
+        self.reserve = bytes;
+        self
+    }
+
+    /// Opens `path` and memory-maps it according to the configured mode.
+    ///
+    /// # Returns
+    /// A `Result` containing the `Mmap` instance or a `MmapError` if the operation fails.
+    pub fn open(self, path: &path::Path) -> Result<Mmap, MmapError> {
+        Mmap::with_mode(path, self.mode, self.reserve)
+    }
+}
+
+/// Access-pattern hints passed to the kernel via `madvise` to influence readahead and
+/// page cache behavior for a mapped region.
+#[derive(Copy, Clone, Debug)]
+pub enum Advice {
+    /// No special treatment. This is the kernel's default behavior.
+    Normal,
+    /// Expect page references in random order. The kernel does not do readahead.
+    Random,
+    /// Expect page references in sequential order. The kernel aggressively reads ahead.
+    Sequential,
+    /// Expect access in the near future. The kernel reads ahead.
+    WillNeed,
+    /// Do not expect access in the near future. The kernel may free resources.
+    DontNeed,
+    /// Linux-specific: free the given range; subsequent access to it is undefined until
+    /// it is written to again.
+    #[cfg(target_os = "linux")]
+    Free,
+    /// Linux-specific: free the underlying pages and clear them from the page cache.
+    #[cfg(target_os = "linux")]
+    Remove,
+}
+
+impl Advice {
+    fn to_libc(self) -> libc::c_int {
+        match self {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+            #[cfg(target_os = "linux")]
+            Advice::Free => libc::MADV_FREE,
+            #[cfg(target_os = "linux")]
+            Advice::Remove => libc::MADV_REMOVE,
+        }
+    }
+}
+
+/// Returns the system's page size, as reported by `sysconf(_SC_PAGESIZE)`.
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Generates paired `read_*`/`write_*` accessors for a numeric type `$ty` in little-endian,
+/// big-endian and native-endian flavors, named after the six identifiers that follow it.
+macro_rules! numeric_accessor {
+    ($ty:ty, $read_le:ident, $read_be:ident, $read_ne:ident, $write_le:ident, $write_be:ident, $write_ne:ident) => {
+        #[doc = concat!("Reads a little-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $read_le(&self, offset: usize) -> Result<$ty, io::Error> {
+            Ok(<$ty>::from_le_bytes(self.read_bytes(offset)?))
+        }
+
+        #[doc = concat!("Reads a big-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $read_be(&self, offset: usize) -> Result<$ty, io::Error> {
+            Ok(<$ty>::from_be_bytes(self.read_bytes(offset)?))
+        }
+
+        #[doc = concat!("Reads a native-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $read_ne(&self, offset: usize) -> Result<$ty, io::Error> {
+            Ok(<$ty>::from_ne_bytes(self.read_bytes(offset)?))
+        }
+
+        #[doc = concat!("Writes `value` as a little-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $write_le(&self, offset: usize, value: $ty) -> Result<(), io::Error> {
+            self.write_bytes(offset, value.to_le_bytes())
+        }
+
+        #[doc = concat!("Writes `value` as a big-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $write_be(&self, offset: usize, value: $ty) -> Result<(), io::Error> {
+            self.write_bytes(offset, value.to_be_bytes())
+        }
+
+        #[doc = concat!("Writes `value` as a native-endian `", stringify!($ty), "` at `offset`.")]
+        pub fn $write_ne(&self, offset: usize, value: $ty) -> Result<(), io::Error> {
+            self.write_bytes(offset, value.to_ne_bytes())
+        }
+    };
 }
 
 impl Mmap {
@@ -54,22 +222,44 @@ impl Mmap {
     /// - `ErrNoMem`: There is not enough memory available to complete the operation.
     /// - `ErrUnknown(code)`: An unknown error occurred with the given OS error code.
     pub fn new(path: &path::Path) -> Result<Self, MmapError> {
-        let file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .truncate(false)
-            .create(true)
-            .open(path)
-            .unwrap();
+        Self::with_mode(path, MapMode::ReadWrite, DEFAULT_RESERVE)
+    }
+
+    /// Opens `path` and memory-maps it in the given `mode` with the given initial virtual
+    /// memory reservation. Shared by `new` and `MmapOptions::open`.
+    fn with_mode(path: &path::Path, mode: MapMode, reserve: usize) -> Result<Self, MmapError> {
+        let file = match mode {
+            // `MAP_PRIVATE` never requires a writable fd, and opening read-only lets
+            // copy-on-write maps work over files the caller has no write access to.
+            MapMode::ReadOnly | MapMode::CopyOnWrite => fs::OpenOptions::new().read(true).open(path),
+            MapMode::ReadWrite => fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .truncate(false)
+                .create(true)
+                .open(path),
+        }
+        .unwrap();
 
         let size = file.metadata().unwrap().len();
 
+        let mut capacity = std::cmp::max(reserve, page_size());
+        while (capacity as u64) < size {
+            capacity *= 2;
+        }
+
+        let (prot, flags) = match mode {
+            MapMode::ReadWrite => (libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED),
+            MapMode::ReadOnly => (libc::PROT_READ, libc::MAP_SHARED),
+            MapMode::CopyOnWrite => (libc::PROT_READ | libc::PROT_WRITE, libc::MAP_PRIVATE),
+        };
+
         unsafe {
             let r = libc::mmap(
                 std::ptr::null::<*const u8>() as *mut libc::c_void,
-                1 << 40,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED,
+                capacity,
+                prot,
+                flags,
                 file.as_raw_fd(),
                 0,
             );
@@ -87,9 +277,61 @@ impl Mmap {
                 )
             } else {
                 let ptr = r as *mut u8;
-                Ok(Mmap { ptr, file, size })
+                Ok(Mmap {
+                    ptr,
+                    file,
+                    size,
+                    capacity,
+                    mode,
+                })
+            }
+        }
+    }
+
+    /// Advises the kernel of the expected access pattern for the whole live region of the
+    /// mapped file, i.e. `[ptr, ptr+size)`.
+    ///
+    /// # Arguments
+    /// * `advice` - The access pattern hint to pass to `madvise`.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or a `MmapError` if the operation fails.
+    pub fn advise(&self, advice: Advice) -> Result<(), MmapError> {
+        self.advise_range(0, self.size as usize, advice)
+    }
+
+    /// Advises the kernel of the expected access pattern for a byte range of the mapped
+    /// file.
+    ///
+    /// # Arguments
+    /// * `offset` - The offset in the file where the range starts.
+    /// * `len` - The length of the range.
+    /// * `advice` - The access pattern hint to pass to `madvise`.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or a `MmapError` if the operation fails.
+    pub fn advise_range(&self, offset: usize, len: usize, advice: Advice) -> Result<(), MmapError> {
+        let (aligned_addr, aligned_len) = self.aligned_region(offset, len)?;
+
+        unsafe {
+            let r = libc::madvise(
+                aligned_addr as *mut libc::c_void,
+                aligned_len,
+                advice.to_libc(),
+            );
+
+            if r != 0 {
+                return Err(
+                    match io::Error::last_os_error().raw_os_error().unwrap_or(-1) {
+                        libc::EINVAL => ErrUnaligned,
+                        libc::ENOMEM => ErrNoMem,
+                        code => ErrUnknown(code as isize),
+                    },
+                );
             }
         }
+
+        Ok(())
     }
 
     /// Appends the given data to the end of the memory-mapped file.
@@ -115,16 +357,61 @@ impl Mmap {
     where
         F: FnOnce(&mut [u8]),
     {
-        self.file.set_len(self.size + len as u64)?;
+        self.ensure_resizable()?;
+        let new_size = self.size + len as u64;
+        if new_size as usize > self.capacity {
+            self.grow_to(new_size as usize)?;
+        }
+        self.file.set_len(new_size)?;
         let slice = unsafe {
             std::slice::from_raw_parts_mut(self.ptr.wrapping_offset(self.size as isize), len)
         };
         writer(slice);
-        self.size += len as u64;
-        self.file.sync_all()?;
+        self.size = new_size;
+        Ok(())
+    }
+
+    /// Enlarges the virtual memory reservation so that it covers at least `required` bytes,
+    /// by repeatedly doubling `self.capacity` and growing the mapping with
+    /// `mremap(MREMAP_MAYMOVE)`, which may relocate it.
+    #[cfg(target_os = "linux")]
+    fn grow_to(&mut self, required: usize) -> Result<(), io::Error> {
+        let mut new_capacity = self.capacity;
+        while new_capacity < required {
+            new_capacity *= 2;
+        }
+
+        unsafe {
+            let r = libc::mremap(
+                self.ptr as *mut libc::c_void,
+                self.capacity,
+                new_capacity,
+                libc::MREMAP_MAYMOVE,
+            );
+
+            if r == libc::MAP_FAILED {
+                return Err(io::Error::new(
+                    io::ErrorKind::OutOfMemory,
+                    match io::Error::last_os_error().raw_os_error().unwrap_or(-1) {
+                        libc::ENOMEM => ErrNoMem,
+                        code => ErrUnknown(code as isize),
+                    },
+                ));
+            }
+
+            self.ptr = r as *mut u8;
+        }
+
+        self.capacity = new_capacity;
         Ok(())
     }
 
+    /// `mremap` is Linux-specific; there is no portable growth strategy elsewhere.
+    #[cfg(not(target_os = "linux"))]
+    fn grow_to(&mut self, _required: usize) -> Result<(), io::Error> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, ErrNoMapSupport))
+    }
+
     /// Overwrites the data at the specified offset in the memory-mapped file.
     ///
     /// # Arguments
@@ -150,15 +437,95 @@ impl Mmap {
     where
         F: FnOnce(&mut [u8]),
     {
-        if offset + len > self.size as usize {
-            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
-        }
+        self.ensure_writable()?;
+        self.check_range(offset, len)?;
 
-        let slice = unsafe {
-            std::slice::from_raw_parts_mut(self.ptr.wrapping_offset(offset as isize), len)
-        };
+        let slice =
+            unsafe { std::slice::from_raw_parts_mut(self.ptr.wrapping_add(offset), len) };
         writer(slice);
-        self.file.sync_all()?;
+        Ok(())
+    }
+
+    /// Returns `MmapError::ErrReadOnly` if this `Mmap` was opened with
+    /// `MmapOptions::read_only`.
+    fn ensure_writable(&self) -> Result<(), io::Error> {
+        if self.mode == MapMode::ReadOnly {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, ErrReadOnly));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if this `Mmap`'s logical size cannot change: `ErrReadOnly` for
+    /// read-only maps, `ErrFixedSize` for copy-on-write maps (growing or shrinking one would
+    /// require changing the backing file's length, which copy-on-write maps never do).
+    fn ensure_resizable(&self) -> Result<(), io::Error> {
+        match self.mode {
+            MapMode::ReadOnly => Err(io::Error::new(io::ErrorKind::PermissionDenied, ErrReadOnly)),
+            MapMode::CopyOnWrite => Err(io::Error::new(io::ErrorKind::Unsupported, ErrFixedSize)),
+            MapMode::ReadWrite => Ok(()),
+        }
+    }
+
+    /// Flushes the whole live region of the mapped file to disk, blocking until the sync
+    /// completes.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or a `MmapError` if the operation fails.
+    pub fn flush(&self) -> Result<(), MmapError> {
+        if self.mode == MapMode::CopyOnWrite {
+            return Ok(());
+        }
+        self.msync(0, self.size as usize, libc::MS_SYNC)
+    }
+
+    /// Queues a flush of the whole live region of the mapped file to disk and returns
+    /// immediately, without waiting for the sync to complete.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or a `MmapError` if the operation fails.
+    pub fn flush_async(&self) -> Result<(), MmapError> {
+        if self.mode == MapMode::CopyOnWrite {
+            return Ok(());
+        }
+        self.msync(0, self.size as usize, libc::MS_ASYNC)
+    }
+
+    /// Flushes a byte range of the mapped file to disk, blocking until the sync completes.
+    /// Use this after a batch of `overwrite`/`append` calls to persist only the bytes that
+    /// were actually touched.
+    ///
+    /// # Arguments
+    /// * `offset` - The offset in the file where the range starts.
+    /// * `len` - The length of the range.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or a `MmapError` if the operation fails.
+    pub fn flush_range(&self, offset: usize, len: usize) -> Result<(), MmapError> {
+        if self.mode == MapMode::CopyOnWrite {
+            return Ok(());
+        }
+        self.msync(offset, len, libc::MS_SYNC)
+    }
+
+    /// Calls `msync` on the page-aligned region covering `[offset, offset+len)`.
+    fn msync(&self, offset: usize, len: usize, flags: libc::c_int) -> Result<(), MmapError> {
+        let (aligned_addr, aligned_len) = self.aligned_region(offset, len)?;
+
+        unsafe {
+            let r = libc::msync(aligned_addr as *mut libc::c_void, aligned_len, flags);
+
+            if r != 0 {
+                return Err(
+                    match io::Error::last_os_error().raw_os_error().unwrap_or(-1) {
+                        libc::EINVAL => ErrUnaligned,
+                        libc::ENOMEM => ErrNoMem,
+                        libc::EBUSY => ErrBusy,
+                        code => ErrUnknown(code as isize),
+                    },
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -170,6 +537,8 @@ impl Mmap {
     /// # Returns
     /// A `Result` containing the unit type (`()`) or an `io::Error` if the operation fails.
     pub fn drop_from_tail(&mut self, len: usize) -> Result<(), io::Error> {
+        self.ensure_resizable()?;
+        self.check_range(0, len)?;
         self.file.set_len(self.size - len as u64)?;
         self.file.sync_all()?;
         self.size -= len as u64;
@@ -185,6 +554,8 @@ impl Mmap {
     /// # Returns
     /// A `Result` containing the number of bytes read or an `io::Error` if the operation fails.
     pub fn read(&self, offset: usize, len: usize) -> Result<Vec<u8>, io::Error> {
+        self.check_range(offset, len)?;
+
         let mut buf = vec![0u8; len];
         self.read_with(offset, len, |b| buf.copy_from_slice(b))?;
 
@@ -204,18 +575,149 @@ impl Mmap {
     where
         F: FnOnce(&[u8]),
     {
-        if offset + len > self.size as usize {
-            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        self.check_range(offset, len)?;
+
+        let slice =
+            unsafe { std::slice::from_raw_parts_mut(self.ptr.wrapping_add(offset), len) };
+        reader(slice);
+        Ok(())
+    }
+
+    /// Computes `offset + len`, returning an error if it overflows `usize` or does not fit
+    /// within the mapped file's current logical size.
+    fn check_range(&self, offset: usize, len: usize) -> Result<usize, io::Error> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, ErrRangeOverflow))?;
+        if end > self.size as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                ErrInvalidRange(offset, len, self.size as usize),
+            ));
         }
+        Ok(end)
+    }
+
+    /// Validates `[offset, offset+len)` against `self.size` with overflow-safe arithmetic
+    /// (like `check_range`), then returns the page-aligned `(addr, len)` pair to pass to
+    /// `madvise`/`msync`.
+    fn aligned_region(&self, offset: usize, len: usize) -> Result<(usize, usize), MmapError> {
+        let end = offset.checked_add(len).ok_or(ErrRangeOverflow)?;
+        if end > self.size as usize {
+            return Err(ErrInvalidRange(offset, len, self.size as usize));
+        }
+
+        let page_size = page_size();
+        let addr = self.ptr.wrapping_add(offset) as usize;
+        let aligned_addr = addr & !(page_size - 1);
+        let aligned_len = (addr - aligned_addr)
+            .checked_add(len)
+            .ok_or(ErrRangeOverflow)?;
+
+        Ok((aligned_addr, aligned_len))
+    }
+
+    /// Reads exactly `N` bytes at `offset` into a stack-allocated array, bounds-checked
+    /// with an overflow-safe addition. `N` is inferred from the caller's context (e.g. the
+    /// array size expected by `u32::from_le_bytes`).
+    fn read_bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N], io::Error> {
+        self.check_range(offset, N)?;
+
+        let mut buf = [0u8; N];
+        let slice =
+            unsafe { std::slice::from_raw_parts(self.ptr.wrapping_add(offset), N) };
+        buf.copy_from_slice(slice);
+        Ok(buf)
+    }
+
+    /// Writes exactly `N` bytes at `offset`, bounds-checked with an overflow-safe addition.
+    fn write_bytes<const N: usize>(&self, offset: usize, bytes: [u8; N]) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        self.check_range(offset, N)?;
 
         let slice = unsafe {
-            std::slice::from_raw_parts_mut(self.ptr.wrapping_offset(offset as isize), len)
+            std::slice::from_raw_parts_mut(self.ptr.wrapping_add(offset), N)
         };
-        reader(slice);
+        slice.copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    numeric_accessor!(u16, read_u16_le, read_u16_be, read_u16_ne, write_u16_le, write_u16_be, write_u16_ne);
+    numeric_accessor!(u32, read_u32_le, read_u32_be, read_u32_ne, write_u32_le, write_u32_be, write_u32_ne);
+    numeric_accessor!(u64, read_u64_le, read_u64_be, read_u64_ne, write_u64_le, write_u64_be, write_u64_ne);
+    numeric_accessor!(i16, read_i16_le, read_i16_be, read_i16_ne, write_i16_le, write_i16_be, write_i16_ne);
+    numeric_accessor!(i32, read_i32_le, read_i32_be, read_i32_ne, write_i32_le, write_i32_be, write_i32_ne);
+    numeric_accessor!(i64, read_i64_le, read_i64_be, read_i64_ne, write_i64_le, write_i64_be, write_i64_ne);
+    numeric_accessor!(f32, read_f32_le, read_f32_be, read_f32_ne, write_f32_le, write_f32_be, write_f32_ne);
+    numeric_accessor!(f64, read_f64_le, read_f64_be, read_f64_ne, write_f64_le, write_f64_be, write_f64_ne);
+
+    /// Reads a `#[repr(C)]` POD value of type `T` directly out of the mapped region at
+    /// `offset`, via `copy_nonoverlapping`, without going through an intermediate byte
+    /// buffer or a serialization layer.
+    ///
+    /// # Arguments
+    /// * `offset` - The offset in the file where the value starts.
+    ///
+    /// # Returns
+    /// A `Result` containing the value or an `io::Error` if the read would go out of bounds.
+    pub fn read_at<T: bytemuck::Pod>(&self, offset: usize) -> Result<T, io::Error> {
+        let size = std::mem::size_of::<T>();
+        self.check_range(offset, size)?;
+
+        unsafe {
+            let mut value = std::mem::MaybeUninit::<T>::uninit();
+            std::ptr::copy_nonoverlapping(
+                self.ptr.wrapping_add(offset),
+                value.as_mut_ptr() as *mut u8,
+                size,
+            );
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Writes a `#[repr(C)]` POD value of type `T` directly into the mapped region at
+    /// `offset`, via `copy_nonoverlapping`, without going through an intermediate byte
+    /// buffer or a serialization layer.
+    ///
+    /// # Arguments
+    /// * `offset` - The offset in the file where the value should be written.
+    /// * `value` - The value to write.
+    ///
+    /// # Returns
+    /// A `Result` containing the unit type (`()`) or an `io::Error` if the write would go
+    /// out of bounds.
+    pub fn write_at<T: bytemuck::Pod>(&self, offset: usize, value: &T) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        let size = std::mem::size_of::<T>();
+        self.check_range(offset, size)?;
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                value as *const T as *const u8,
+                self.ptr.wrapping_add(offset),
+                size,
+            );
+        }
+
         Ok(())
     }
 }
 
+impl Drop for Mmap {
+    /// Unmaps the virtual memory reservation. Failure is logged and otherwise ignored,
+    /// since there is nothing meaningful a `Drop` impl can do about it.
+    fn drop(&mut self) {
+        unsafe {
+            if libc::munmap(self.ptr as *mut libc::c_void, self.capacity) != 0 {
+                eprintln!(
+                    "mmap-simple: munmap failed: {}",
+                    io::Error::last_os_error()
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -276,4 +778,107 @@ mod tests {
         let read = mmap_file.read(1, 3).unwrap();
         assert_eq!(read, "xxw".as_bytes());
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn advise_range_rejects_out_of_bounds() {
+        let mut mmap_file = setup_file();
+        mmap_file.append("hello".as_bytes()).unwrap();
+
+        mmap_file.advise(Advice::Sequential).unwrap();
+        mmap_file.advise_range(1, 3, Advice::Random).unwrap();
+
+        let r = mmap_file.advise_range(1, usize::MAX, Advice::Random);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn flush_variants_sync_to_disk() {
+        let mut mmap_file = setup_file();
+        mmap_file.append("hello".as_bytes()).unwrap();
+
+        mmap_file.flush().unwrap();
+        mmap_file.flush_async().unwrap();
+        mmap_file.flush_range(0, 5).unwrap();
+
+        check_result("hello".as_bytes());
+    }
+
+    #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+    #[repr(C)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn typed_accessors_round_trip() {
+        let mut mmap_file = setup_file();
+        mmap_file.append(&[0u8; 16]).unwrap();
+
+        mmap_file.write_u32_le(0, 0xdead_beef).unwrap();
+        assert_eq!(mmap_file.read_u32_le(0).unwrap(), 0xdead_beef);
+
+        mmap_file.write_at(8, &Point { x: -1, y: 42 }).unwrap();
+        let point: Point = mmap_file.read_at(8).unwrap();
+        assert_eq!((point.x, point.y), (-1, 42));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn drop_unmaps_and_out_of_range_reads_error() {
+        let mut mmap_file = setup_file();
+        mmap_file.append("hello".as_bytes()).unwrap();
+
+        let r = mmap_file.read(1, usize::MAX);
+        assert!(r.is_err());
+
+        drop(mmap_file);
+
+        let reopened = Mmap::new(path::Path::new(TEST_FILE)).unwrap();
+        assert_eq!(reopened.read(0, 5).unwrap(), "hello".as_bytes());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn read_only_and_copy_on_write_modes() {
+        let mut mmap_file = setup_file();
+        mmap_file.append("hello".as_bytes()).unwrap();
+        drop(mmap_file);
+
+        let path = path::Path::new(TEST_FILE);
+
+        let read_only = MmapOptions::new().read_only().open(path).unwrap();
+        assert_eq!(read_only.read(0, 5).unwrap(), "hello".as_bytes());
+        assert!(read_only.overwrite(0, "world".as_bytes()).is_err());
+        drop(read_only);
+
+        let mut cow = MmapOptions::new().copy_on_write().open(path).unwrap();
+        cow.overwrite(0, "world".as_bytes()).unwrap();
+        assert_eq!(cow.read(0, 5).unwrap(), "world".as_bytes());
+        assert!(cow.append("!".as_bytes()).is_err());
+        drop(cow);
+
+        // The copy-on-write write never reached the backing file.
+        check_result("hello".as_bytes());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn mremap_grows_mapping_past_initial_reserve() {
+        let path = path::Path::new(TEST_FILE);
+        let _ = fs::remove_file(path);
+        let mut mmap_file = MmapOptions::new().reserve(1).open(path).unwrap();
+
+        // The reserve is rounded up to at least a page, so this forces at least one
+        // `mremap` growth.
+        let data = vec![b'x'; page_size() + 1];
+        mmap_file.append(&data).unwrap();
+        assert_eq!(mmap_file.read(0, data.len()).unwrap(), data);
+
+        drop(mmap_file);
+        check_result(&data);
+    }
 }